@@ -15,6 +15,7 @@
  * - Total: ~115MB (vs ~145MB with INT8)
  */
 
+use sha2::{Digest, Sha256};
 use wasm_bindgen::prelude::*;
 
 // Embed all models at compile time using include_bytes!().
@@ -22,118 +23,222 @@ use wasm_bindgen::prelude::*;
 //
 // Feature flags:
 // - `no-models`: Build without embedding models (for testing build scripts).
-// - `minilm-only`: Build with only MiniLM model (~17 MB).
-// - `codet5-only`: Build with only CodeT5 model (~90 MB).
+// - `embeddings`: Embed the MiniLM model and its tokenizer (~17 MB).
+// - `summarization`: Embed the CodeT5 encoder/decoder and their tokenizer (~90 MB).
+// - `layout`: Embed Yoga Layout (~95 KB).
 // - `unoptimized-wasm`: Use unoptimized WASM files for faster iteration.
+// - `compressed-models`: Store model/tokenizer bytes as zstd frames instead
+//   of raw weights, shrinking the data section. Purely an internal storage
+//   detail — `model_ptr_by_name`/`model_size_by_name` and the
+//   `get_<model>_ptr`/`get_<model>_size` accessors always hand back
+//   decompressed bytes (see `resolved_bytes`), never a raw zstd frame.
+// - `threaded-ort`: Embed the WASI-threads build of ONNX Runtime instead of
+//   the SIMD-only single-threaded default; see `get_onnx_runtime_is_threaded`.
+// - `external-models`: Drop the `include_bytes!` weights below entirely and
+//   serve bytes registered at runtime via `register_external_model` instead,
+//   for hosts that fetch/cache models out-of-band (CDN, disk cache, signed
+//   delivery) rather than baking them into the binary.
+//
+// `embeddings`, `summarization`, and `layout` are independent and all enabled
+// by default, so `--no-default-features --features embeddings` compiles
+// without the CodeT5 files present.
 
-#[cfg(all(not(feature = "no-models"), not(feature = "codet5-only")))]
+#[cfg(all(
+    feature = "embeddings",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    not(feature = "compressed-models")
+))]
 static MINILM_MODEL: &[u8] = include_bytes!("../../../../.cache/models/minilm-int4.onnx");
-#[cfg(any(feature = "no-models", feature = "codet5-only"))]
+#[cfg(all(
+    feature = "embeddings",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    feature = "compressed-models"
+))]
+static MINILM_MODEL: &[u8] = include_bytes!("../../../../.cache/models/minilm-int4.onnx.zst");
+#[cfg(any(feature = "no-models", not(feature = "embeddings"), feature = "external-models"))]
 static MINILM_MODEL: &[u8] = &[];
 
-#[cfg(all(not(feature = "no-models"), not(feature = "codet5-only")))]
+#[cfg(all(
+    feature = "embeddings",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    not(feature = "compressed-models")
+))]
 static MINILM_TOKENIZER: &[u8] = include_bytes!("../../../../.cache/models/minilm-tokenizer.json");
-#[cfg(any(feature = "no-models", feature = "codet5-only"))]
+#[cfg(all(
+    feature = "embeddings",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    feature = "compressed-models"
+))]
+static MINILM_TOKENIZER: &[u8] = include_bytes!("../../../../.cache/models/minilm-tokenizer.json.zst");
+#[cfg(any(feature = "no-models", not(feature = "embeddings"), feature = "external-models"))]
 static MINILM_TOKENIZER: &[u8] = &[];
 
-#[cfg(all(not(feature = "no-models"), not(feature = "minilm-only")))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    not(feature = "compressed-models")
+))]
 static CODET5_ENCODER: &[u8] = include_bytes!("../../../../.cache/models/codet5-encoder-int4.onnx");
-#[cfg(any(feature = "no-models", feature = "minilm-only"))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    feature = "compressed-models"
+))]
+static CODET5_ENCODER: &[u8] = include_bytes!("../../../../.cache/models/codet5-encoder-int4.onnx.zst");
+#[cfg(any(feature = "no-models", not(feature = "summarization"), feature = "external-models"))]
 static CODET5_ENCODER: &[u8] = &[];
 
-#[cfg(all(not(feature = "no-models"), not(feature = "minilm-only")))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    not(feature = "compressed-models")
+))]
 static CODET5_DECODER: &[u8] = include_bytes!("../../../../.cache/models/codet5-decoder-int4.onnx");
-#[cfg(any(feature = "no-models", feature = "minilm-only"))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    feature = "compressed-models"
+))]
+static CODET5_DECODER: &[u8] = include_bytes!("../../../../.cache/models/codet5-decoder-int4.onnx.zst");
+#[cfg(any(feature = "no-models", not(feature = "summarization"), feature = "external-models"))]
 static CODET5_DECODER: &[u8] = &[];
 
-#[cfg(all(not(feature = "no-models"), not(feature = "minilm-only")))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    not(feature = "compressed-models")
+))]
 static CODET5_TOKENIZER: &[u8] = include_bytes!("../../../../.cache/models/codet5-tokenizer.json");
-#[cfg(any(feature = "no-models", feature = "minilm-only"))]
+#[cfg(all(
+    feature = "summarization",
+    not(feature = "no-models"),
+    not(feature = "external-models"),
+    feature = "compressed-models"
+))]
+static CODET5_TOKENIZER: &[u8] = include_bytes!("../../../../.cache/models/codet5-tokenizer.json.zst");
+#[cfg(any(feature = "no-models", not(feature = "summarization"), feature = "external-models"))]
 static CODET5_TOKENIZER: &[u8] = &[];
 
-// Use optimized SIMD-only WASM (single-threaded).
-// We don't use multi-threading (no session options, sequential batching).
-// SIMD-only saves ~2 MB vs threaded version.
-#[cfg(all(not(feature = "unoptimized-wasm"), not(feature = "no-models")))]
-static ONNX_RUNTIME: &[u8] = include_bytes!("../../../../.cache/models/ort-wasm-simd-threaded.wasm");
-#[cfg(all(feature = "unoptimized-wasm", not(feature = "no-models")))]
+// Default to optimized SIMD-only WASM (single-threaded, sequential batching,
+// no session options); SIMD-only saves ~2 MB vs the threaded build below.
+// Only needed when a model that actually runs through ONNX Runtime is embedded.
+#[cfg(all(
+    any(feature = "embeddings", feature = "summarization"),
+    not(feature = "no-models"),
+    not(feature = "threaded-ort"),
+    not(feature = "unoptimized-wasm")
+))]
+static ONNX_RUNTIME: &[u8] = include_bytes!("../../../../.cache/models/ort-wasm-simd-optimized.wasm");
+#[cfg(all(
+    any(feature = "embeddings", feature = "summarization"),
+    not(feature = "no-models"),
+    not(feature = "threaded-ort"),
+    feature = "unoptimized-wasm"
+))]
+static ONNX_RUNTIME: &[u8] = include_bytes!("../../../../.cache/models/ort-wasm-simd.wasm");
+// WASI-threads build: enables intra-op parallelism for CodeT5 decoding on
+// hosts that support SharedArrayBuffer. Pair with the session-options
+// accessors below so the JS glue actually configures threaded execution.
+#[cfg(all(
+    any(feature = "embeddings", feature = "summarization"),
+    not(feature = "no-models"),
+    feature = "threaded-ort"
+))]
 static ONNX_RUNTIME: &[u8] = include_bytes!("../../../../.cache/models/ort-wasm-simd-threaded.wasm");
-#[cfg(feature = "no-models")]
+#[cfg(any(feature = "no-models", not(any(feature = "embeddings", feature = "summarization"))))]
 static ONNX_RUNTIME: &[u8] = &[];
 
-#[cfg(all(not(feature = "unoptimized-wasm"), not(feature = "no-models")))]
-static YOGA_LAYOUT: &[u8] = include_bytes!("../../../../.cache/models/yoga.wasm");
-#[cfg(all(feature = "unoptimized-wasm", not(feature = "no-models")))]
+#[cfg(all(feature = "layout", not(feature = "no-models")))]
 static YOGA_LAYOUT: &[u8] = include_bytes!("../../../../.cache/models/yoga.wasm");
-#[cfg(feature = "no-models")]
+#[cfg(any(feature = "no-models", not(feature = "layout")))]
 static YOGA_LAYOUT: &[u8] = &[];
 
 // =============================================================================
 // MiniLM Model
 // =============================================================================
 
-/// Get pointer to MiniLM model in WASM linear memory.
+/// Get pointer to MiniLM model in WASM linear memory. With `compressed-models`
+/// this transparently decompresses (see `model_ptr_by_name`) — never the raw
+/// zstd frame — so callers don't need to know storage is compressed.
 #[wasm_bindgen]
 pub fn get_minilm_model_ptr() -> *const u8 {
-    MINILM_MODEL.as_ptr()
+    model_ptr_by_name("minilm")
 }
 
-/// Get size of MiniLM model in bytes.
+/// Get size of MiniLM model in bytes (decompressed size; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_minilm_model_size() -> usize {
-    MINILM_MODEL.len()
+    model_size_by_name("minilm")
 }
 
-/// Get pointer to MiniLM tokenizer in WASM linear memory.
+/// Get pointer to MiniLM tokenizer in WASM linear memory (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_minilm_tokenizer_ptr() -> *const u8 {
-    MINILM_TOKENIZER.as_ptr()
+    model_ptr_by_name("minilm-tokenizer")
 }
 
-/// Get size of MiniLM tokenizer in bytes.
+/// Get size of MiniLM tokenizer in bytes (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_minilm_tokenizer_size() -> usize {
-    MINILM_TOKENIZER.len()
+    model_size_by_name("minilm-tokenizer")
 }
 
 // =============================================================================
 // CodeT5 Models
 // =============================================================================
 
-/// Get pointer to CodeT5 encoder in WASM linear memory.
+/// Get pointer to CodeT5 encoder in WASM linear memory (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_encoder_ptr() -> *const u8 {
-    CODET5_ENCODER.as_ptr()
+    model_ptr_by_name("codet5-encoder")
 }
 
-/// Get size of CodeT5 encoder in bytes.
+/// Get size of CodeT5 encoder in bytes (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_encoder_size() -> usize {
-    CODET5_ENCODER.len()
+    model_size_by_name("codet5-encoder")
 }
 
-/// Get pointer to CodeT5 decoder in WASM linear memory.
+/// Get pointer to CodeT5 decoder in WASM linear memory (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_decoder_ptr() -> *const u8 {
-    CODET5_DECODER.as_ptr()
+    model_ptr_by_name("codet5-decoder")
 }
 
-/// Get size of CodeT5 decoder in bytes.
+/// Get size of CodeT5 decoder in bytes (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_decoder_size() -> usize {
-    CODET5_DECODER.len()
+    model_size_by_name("codet5-decoder")
 }
 
-/// Get pointer to CodeT5 tokenizer in WASM linear memory.
+/// Get pointer to CodeT5 tokenizer in WASM linear memory (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_tokenizer_ptr() -> *const u8 {
-    CODET5_TOKENIZER.as_ptr()
+    model_ptr_by_name("codet5-tokenizer")
 }
 
-/// Get size of CodeT5 tokenizer in bytes.
+/// Get size of CodeT5 tokenizer in bytes (decompressed; see
+/// `get_minilm_model_ptr`).
 #[wasm_bindgen]
 pub fn get_codet5_tokenizer_size() -> usize {
-    CODET5_TOKENIZER.len()
+    model_size_by_name("codet5-tokenizer")
 }
 
 // =============================================================================
@@ -152,6 +257,46 @@ pub fn get_onnx_runtime_size() -> usize {
     ONNX_RUNTIME.len()
 }
 
+/// Whether the embedded ONNX Runtime build supports WASI threads. The JS
+/// glue should only pass the recommended session options below when this
+/// returns `true` (the host also needs SharedArrayBuffer support).
+#[wasm_bindgen(js_name = getOnnxRuntimeIsThreaded)]
+pub fn get_onnx_runtime_is_threaded() -> bool {
+    cfg!(feature = "threaded-ort")
+}
+
+/// Recommended `interOpNumThreads` for the threaded ONNX Runtime build.
+/// Meaningless when `get_onnx_runtime_is_threaded()` is `false`.
+#[wasm_bindgen(js_name = getRecommendedInterOpThreads)]
+pub fn get_recommended_inter_op_threads() -> u32 {
+    1
+}
+
+/// Recommended `intraOpNumThreads` for the threaded ONNX Runtime build.
+/// Meaningless when `get_onnx_runtime_is_threaded()` is `false`.
+#[wasm_bindgen(js_name = getRecommendedIntraOpThreads)]
+pub fn get_recommended_intra_op_threads() -> u32 {
+    4
+}
+
+/// Recommended ORT session options as a JSON blob, ready to pass straight
+/// through to `ort.InferenceSession.create()`. Mirrors the scalar accessors
+/// above so callers can take either the parsed object or the individual
+/// fields.
+#[wasm_bindgen(js_name = getRecommendedSessionOptionsJson)]
+pub fn get_recommended_session_options_json() -> String {
+    format!(
+        "{{\"executionMode\":\"{}\",\"interOpNumThreads\":{},\"intraOpNumThreads\":{}}}",
+        if get_onnx_runtime_is_threaded() {
+            "parallel"
+        } else {
+            "sequential"
+        },
+        get_recommended_inter_op_threads(),
+        get_recommended_intra_op_threads(),
+    )
+}
+
 // =============================================================================
 // Yoga Layout
 // =============================================================================
@@ -168,6 +313,328 @@ pub fn get_yoga_layout_size() -> usize {
     YOGA_LAYOUT.len()
 }
 
+// =============================================================================
+// Model registry
+// =============================================================================
+//
+// A single manifest describing every embedded artifact, so adding or
+// re-quantizing a model only means adding an entry here instead of a new
+// pair of hand-written accessors below.
+
+/// Metadata for one embedded model artifact.
+struct ModelEntry {
+    name: &'static str,
+    kind: &'static str,
+    quantization: &'static str,
+    bytes: &'static [u8],
+}
+
+static MODELS: &[ModelEntry] = &[
+    ModelEntry { name: "minilm", kind: "embedding-model", quantization: "int4", bytes: MINILM_MODEL },
+    ModelEntry { name: "minilm-tokenizer", kind: "tokenizer", quantization: "none", bytes: MINILM_TOKENIZER },
+    ModelEntry { name: "codet5-encoder", kind: "summarization-model", quantization: "int4", bytes: CODET5_ENCODER },
+    ModelEntry { name: "codet5-decoder", kind: "summarization-model", quantization: "int4", bytes: CODET5_DECODER },
+    ModelEntry { name: "codet5-tokenizer", kind: "tokenizer", quantization: "none", bytes: CODET5_TOKENIZER },
+    ModelEntry { name: "onnx-runtime", kind: "runtime", quantization: "none", bytes: ONNX_RUNTIME },
+    ModelEntry { name: "yoga-layout", kind: "runtime", quantization: "none", bytes: YOGA_LAYOUT },
+];
+
+fn find_model(name: &str) -> Option<&'static ModelEntry> {
+    MODELS.iter().find(|entry| entry.name == name)
+}
+
+/// Number of entries in the model manifest.
+#[wasm_bindgen]
+pub fn model_count() -> usize {
+    MODELS.len()
+}
+
+/// Get the manifest name of the model at `index`, or `undefined` if out of range.
+#[wasm_bindgen]
+pub fn model_name(index: usize) -> Option<String> {
+    MODELS.get(index).map(|entry| entry.name.to_string())
+}
+
+/// Get the manifest kind (e.g. `"embedding-model"`, `"tokenizer"`, `"runtime"`)
+/// of the model at `index`, or `undefined` if out of range.
+#[wasm_bindgen]
+pub fn model_kind(index: usize) -> Option<String> {
+    MODELS.get(index).map(|entry| entry.kind.to_string())
+}
+
+/// Get the quantization (e.g. `"int4"`, `"none"`) of the model at `index`,
+/// or `undefined` if out of range.
+#[wasm_bindgen]
+pub fn model_quantization(index: usize) -> Option<String> {
+    MODELS.get(index).map(|entry| entry.quantization.to_string())
+}
+
+/// Get pointer to a named model's bytes in WASM linear memory, or null if
+/// `name` isn't in the manifest, or (with `external-models`) hasn't been
+/// registered yet via `register_external_model`. With `compressed-models`,
+/// this transparently decompresses (via `resolved_bytes`) — compression is
+/// purely an internal storage detail, never visible at this API boundary.
+#[wasm_bindgen]
+pub fn model_ptr_by_name(name: &str) -> *const u8 {
+    #[cfg(feature = "external-models")]
+    {
+        if let Some((ptr, _)) = external_model_bytes(name) {
+            return ptr;
+        }
+        if find_model(name).is_some_and(|entry| entry.kind != "runtime") {
+            return std::ptr::null();
+        }
+    }
+    find_model(name).map(|entry| resolved_bytes(entry).0).unwrap_or(std::ptr::null())
+}
+
+/// Get the size in bytes of a named model, or 0 if `name` isn't in the
+/// manifest, or (with `external-models`) hasn't been registered yet. With
+/// `compressed-models`, this is the decompressed size (see
+/// `model_ptr_by_name`).
+#[wasm_bindgen]
+pub fn model_size_by_name(name: &str) -> usize {
+    #[cfg(feature = "external-models")]
+    {
+        if let Some((_, len)) = external_model_bytes(name) {
+            return len;
+        }
+    }
+    find_model(name).map(|entry| resolved_bytes(entry).1).unwrap_or(0)
+}
+
+/// Get the hex-encoded SHA-256 of a named model's bytes, for host-side
+/// integrity checks before the pointer/size pair is trusted. Hashes the
+/// decompressed bytes (see `model_ptr_by_name`), since that's what the host
+/// actually ends up with.
+#[wasm_bindgen]
+pub fn model_sha256_by_name(name: &str) -> Option<String> {
+    #[cfg(feature = "external-models")]
+    {
+        if let Some((ptr, len)) = external_model_bytes(name) {
+            return Some(hex_sha256(unsafe { std::slice::from_raw_parts(ptr, len) }));
+        }
+        // Known model/tokenizer name but not registered yet: no real bytes to
+        // hash, so don't hand back the meaningless hash of the empty
+        // placeholder static. Runtime entries stay embedded and are hashed below.
+        if find_model(name).is_some_and(|entry| entry.kind != "runtime") {
+            return None;
+        }
+    }
+    find_model(name).map(|entry| {
+        let (ptr, len) = resolved_bytes(entry);
+        hex_sha256(unsafe { std::slice::from_raw_parts(ptr, len) })
+    })
+}
+
+fn hex_sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// =============================================================================
+// External model registration (optional)
+// =============================================================================
+//
+// With `external-models`, the `include_bytes!` statics above are empty and
+// model bytes instead live in this process-wide registry, populated by the
+// host at runtime. Each name's expected SHA-256 is read at compile time from
+// `.cache/models/model-hashes.txt`, so a host can't register the wrong (or
+// tampered) bytes under a known name.
+
+#[cfg(feature = "external-models")]
+static EXTERNAL_MODELS: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>> =
+    std::sync::OnceLock::new();
+
+#[cfg(feature = "external-models")]
+static EXPECTED_HASHES_MANIFEST: &str = include_str!("../../../../.cache/models/model-hashes.txt");
+
+/// Look up the pinned hex-encoded SHA-256 for `name` from the model-pinning
+/// manifest, one `<name> <hex>` pair per line.
+#[cfg(feature = "external-models")]
+fn expected_sha256(name: &str) -> Option<&'static str> {
+    EXPECTED_HASHES_MANIFEST.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let entry_name = parts.next()?;
+        let hash = parts.next()?;
+        (entry_name == name).then_some(hash)
+    })
+}
+
+/// Pointer/length into the registry's own buffer for a registered model.
+/// Stable for the life of the process: entries are inserted at most once
+/// per name, so the backing `Vec` is never reallocated out from under it.
+#[cfg(feature = "external-models")]
+fn external_model_bytes(name: &str) -> Option<(*const u8, usize)> {
+    let registry = EXTERNAL_MODELS.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+    registry.lock().unwrap().get(name).map(|bytes| (bytes.as_ptr(), bytes.len()))
+}
+
+/// Register host-provided bytes for a named model, verifying them against
+/// the pinned SHA-256 before accepting. `ptr`/`len` describe a buffer the
+/// host has already written into this module's linear memory; `sha256` is
+/// the 32-byte digest the host computed independently (e.g. after a CDN
+/// download), checked for defense-in-depth alongside our own hash of the
+/// bytes; an empty `sha256` skips that host-digest check, but the pinned
+/// manifest check always runs. Returns `false` (and registers nothing) if
+/// `name` is unknown, the digest doesn't match the pinned manifest, or a
+/// non-empty `sha256` disagrees with the bytes actually supplied. A name that
+/// is already registered keeps its existing buffer.
+///
+/// # Safety
+/// `ptr` must point to at least `len` readable bytes in this module's linear
+/// memory for the duration of the call.
+#[wasm_bindgen(js_name = registerExternalModel)]
+pub unsafe fn register_external_model(name: &str, ptr: *const u8, len: usize, sha256: &[u8]) -> bool {
+    #[cfg(not(feature = "external-models"))]
+    {
+        let _ = (name, ptr, len, sha256);
+        false
+    }
+    #[cfg(feature = "external-models")]
+    {
+        let Some(expected_hex) = expected_sha256(name) else {
+            return false;
+        };
+        let bytes = std::slice::from_raw_parts(ptr, len);
+        let digest = Sha256::digest(bytes);
+        if !sha256.is_empty() && sha256 != digest.as_slice() {
+            return false;
+        }
+        let actual_hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        if !actual_hex.eq_ignore_ascii_case(expected_hex) {
+            return false;
+        }
+        let registry = EXTERNAL_MODELS.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+        registry.lock().unwrap().entry(name.to_string()).or_insert_with(|| bytes.to_vec());
+        true
+    }
+}
+
+// =============================================================================
+// Compressed model storage (optional)
+// =============================================================================
+//
+// With `compressed-models`, the statics above hold zstd frames instead of raw
+// weights. Each artifact inflates into a cached buffer on first access so
+// repeated inference runs don't re-decompress it. This is purely an internal
+// storage detail: `model_ptr_by_name`/`model_size_by_name` (and the
+// `get_<model>_ptr`/`get_<model>_size` accessors built on them) always hand
+// back decompressed bytes via `resolved_bytes`, so no caller ever sees a raw
+// zstd frame through the normal accessor API regardless of this feature.
+
+#[cfg(feature = "compressed-models")]
+static DECOMPRESS_CACHE: std::sync::OnceLock<std::sync::Mutex<std::collections::HashMap<&'static str, Vec<u8>>>> =
+    std::sync::OnceLock::new();
+
+#[cfg(feature = "compressed-models")]
+fn decompressed_bytes(entry: &'static ModelEntry) -> (*const u8, usize) {
+    let cache = DECOMPRESS_CACHE.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+    let mut cache = cache.lock().unwrap();
+    let bytes = cache
+        .entry(entry.name)
+        .or_insert_with(|| zstd::decode_all(entry.bytes).unwrap_or_default());
+    (bytes.as_ptr(), bytes.len())
+}
+
+/// Pointer/length to `entry`'s bytes as a caller should see them: decompressed
+/// when `compressed-models` is on and the entry is a model/tokenizer (runtime
+/// entries are always embedded raw), the static's own bytes otherwise. The
+/// single chokepoint `model_ptr_by_name`/`model_size_by_name`/
+/// `model_sha256_by_name` route through so compression never leaks past the
+/// manifest accessors.
+fn resolved_bytes(entry: &'static ModelEntry) -> (*const u8, usize) {
+    #[cfg(feature = "compressed-models")]
+    {
+        if entry.kind != "runtime" {
+            return decompressed_bytes(entry);
+        }
+    }
+    (entry.bytes.as_ptr(), entry.bytes.len())
+}
+
+/// Alias for `model_ptr_by_name`, kept for callers already using the explicit
+/// "decompress" name. Decompression now happens transparently inside
+/// `model_ptr_by_name` itself (see `resolved_bytes`), so this no longer does
+/// anything `model_ptr_by_name` doesn't already do on its own.
+#[wasm_bindgen(js_name = decompressModel)]
+pub fn decompress_model(name: &str) -> *const u8 {
+    model_ptr_by_name(name)
+}
+
+/// Alias for `model_size_by_name` (see `decompress_model`).
+#[wasm_bindgen(js_name = decompressedModelSize)]
+pub fn decompressed_model_size(name: &str) -> usize {
+    model_size_by_name(name)
+}
+
+// =============================================================================
+// Memory Estimation
+// =============================================================================
+//
+// Instantiating a model copies its on-disk weights into linear memory and
+// then builds inference arenas/scratch buffers on top, so the peak working
+// set is a multiple of the file size, not the file size itself. These
+// factors are conservative defaults; tune them from real profiling runs
+// rather than trusting them blindly.
+
+/// Dequantization expansion factor for INT4 weights (unpacked into f32
+/// activation/scratch buffers during inference).
+const INT4_DEQUANT_FACTOR: usize = 8;
+
+/// Dequantization expansion factor for INT8 weights.
+const INT8_DEQUANT_FACTOR: usize = 4;
+
+/// Fixed ONNX Runtime arena reservation, added once per `estimate_peak_memory`
+/// call regardless of how many models are named.
+const ORT_ARENA_BYTES: usize = 64 * 1024 * 1024;
+
+/// Flat overhead added per tokenizer/vocab artifact for parsed-token tables
+/// and lookup structures that don't show up in the on-disk JSON size.
+const TOKENIZER_OVERHEAD_BYTES: usize = 4 * 1024 * 1024;
+
+#[wasm_bindgen(js_name = getInt4DequantFactor)]
+pub fn get_int4_dequant_factor() -> usize {
+    INT4_DEQUANT_FACTOR
+}
+
+#[wasm_bindgen(js_name = getInt8DequantFactor)]
+pub fn get_int8_dequant_factor() -> usize {
+    INT8_DEQUANT_FACTOR
+}
+
+#[wasm_bindgen(js_name = getOrtArenaBytes)]
+pub fn get_ort_arena_bytes() -> usize {
+    ORT_ARENA_BYTES
+}
+
+#[wasm_bindgen(js_name = getTokenizerOverheadBytes)]
+pub fn get_tokenizer_overhead_bytes() -> usize {
+    TOKENIZER_OVERHEAD_BYTES
+}
+
+/// Conservative estimate of the peak linear-memory bytes needed to
+/// instantiate the named models, so a host can call `memory.grow` once up
+/// front instead of growing incrementally (or running out mid-inference).
+/// Unknown names are skipped. Adds `get_ort_arena_bytes()` once regardless
+/// of how many models are named, since they all share the same runtime.
+#[wasm_bindgen(js_name = estimatePeakMemory)]
+pub fn estimate_peak_memory(models: Vec<String>) -> usize {
+    let working_set: usize = models
+        .iter()
+        .filter_map(|name| find_model(name))
+        .map(|entry| {
+            let on_disk = resolved_bytes(entry).1;
+            match entry.quantization {
+                "int4" => on_disk * INT4_DEQUANT_FACTOR,
+                "int8" => on_disk * INT8_DEQUANT_FACTOR,
+                _ if entry.kind == "tokenizer" => on_disk + TOKENIZER_OVERHEAD_BYTES,
+                _ => on_disk,
+            }
+        })
+        .sum();
+    working_set + ORT_ARENA_BYTES
+}
+
 // =============================================================================
 // Utility Functions
 // =============================================================================
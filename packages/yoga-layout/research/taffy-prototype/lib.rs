@@ -7,13 +7,145 @@
  * Uses Taffy v0.6.0 - a modern, pure Rust implementation of flexbox layout.
  */
 
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use js_sys::{Array, Function, Reflect};
 use wasm_bindgen::prelude::*;
 use taffy::prelude::*;
 
-/// Wrapper for Taffy Node with Yoga-compatible API.
+/// Sentinel passed as an available-space arg to the measure callback when
+/// Taffy requests a MinContent measurement (no numeric width/height applies).
+const AVAILABLE_SPACE_MIN_CONTENT: f64 = f64::NEG_INFINITY;
+/// Sentinel for a MaxContent measurement request.
+const AVAILABLE_SPACE_MAX_CONTENT: f64 = f64::INFINITY;
+
+/// Flex direction, mirrors CSS `flex-direction`.
 #[wasm_bindgen]
-pub struct YogaNode {
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgFlexDirection {
+    Column,
+    ColumnReverse,
+    Row,
+    RowReverse,
+}
+
+/// `justify-content`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgJustifyContent {
+    FlexStart,
+    Center,
+    FlexEnd,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+/// Shared enum for `align-items`/`align-content`/`align-self`. Not every
+/// variant is representable by every one of Taffy's three distinct align
+/// types; each setter documents how it folds the unsupported ones.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgAlign {
+    Auto,
+    FlexStart,
+    Center,
+    FlexEnd,
+    Stretch,
+    Baseline,
+    SpaceBetween,
+    SpaceAround,
+}
+
+/// `flex-wrap`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgFlexWrap {
+    NoWrap,
+    Wrap,
+    WrapReverse,
+}
+
+/// `display`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgDisplay {
+    Flex,
+    None,
+    Contents,
+    Grid,
+}
+
+/// `position` (CSS position scheme, not coordinates).
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgPositionType {
+    Relative,
+    Absolute,
+}
+
+/// Box edge, used by padding/margin/inset setters.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgEdge {
+    Left,
+    Top,
+    Right,
+    Bottom,
+    Start,
+    End,
+    Horizontal,
+    Vertical,
+    All,
+}
+
+/// Gap axis, used by `setGap`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgGutter {
+    Column,
+    Row,
+    All,
+}
+
+/// `grid-auto-flow`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum YgGridAutoFlow {
+    Row,
+    Column,
+    RowDense,
+    ColumnDense,
+}
+
+// The tree and its out-of-band measure-function/contents-node registries,
+// shared by every `YogaNode` handle that belongs to the same tree (see
+// `YogaNode::adopt`).
+//
+// Taffy has no engine-level `display: contents` (there is no such variant on
+// `taffy::style::Display`), so it's tracked here instead of in `Style`: a
+// node in `contents_nodes` is reparented out of the real layout tree for the
+// duration of `calculate_layout` (see `apply_contents_flattening`) rather
+// than being laid out as a flex/grid container in its own right.
+struct TreeState {
     taffy: TaffyTree,
+    measure_funcs: HashMap<NodeId, Function>,
+    contents_nodes: HashSet<NodeId>,
+}
+
+/// Wrapper for a Taffy Node with a Yoga-compatible API.
+///
+/// Every `YogaNode` holds an `Rc<RefCell<TreeState>>` pointing at the
+/// `TaffyTree` it belongs to, rather than owning a tree outright. A freshly
+/// constructed node is the sole member of its own tree; `insertChild` merges
+/// the child's subtree into the parent's tree (see `adopt`), so after that
+/// call `getChild`/`getParent` can hand back real, independently usable
+/// handles into the same tree.
+#[wasm_bindgen]
+pub struct YogaNode {
+    tree: Rc<RefCell<TreeState>>,
     node: NodeId,
 }
 
@@ -24,31 +156,41 @@ impl YogaNode {
     pub fn new() -> Self {
         let mut taffy = TaffyTree::new();
         let node = taffy.new_leaf(Style::default()).unwrap();
-        YogaNode { taffy, node }
+        let tree = TreeState { taffy, measure_funcs: HashMap::new(), contents_nodes: HashSet::new() };
+        YogaNode { tree: Rc::new(RefCell::new(tree)), node }
     }
 
     // ==========================================================================
     // Node tree management
     // ==========================================================================
 
-    /// Insert a child node at the specified index.
+    /// Insert a child node at the specified index. If `child` belongs to a
+    /// different tree (the common case, since every `new()` node starts its
+    /// own), its subtree is first cloned into this node's tree and `child`
+    /// is repointed at the clone.
     #[wasm_bindgen(js_name = insertChild)]
-    pub fn insert_child(&mut self, child: &YogaNode, _index: u32) {
-        let _ = self.taffy.add_child(self.node, child.node);
-        // Note: Taffy doesn't support insertion at specific index in the same way.
-        // Children are appended. For full compatibility, would need custom reordering.
+    pub fn insert_child(&mut self, child: &mut YogaNode, index: u32) {
+        let child_node = self.adopt(child);
+        let mut tree = self.tree.borrow_mut();
+        let _ = tree.taffy.insert_child_at_index(self.node, index as usize, child_node);
     }
 
     /// Remove a child node.
     #[wasm_bindgen(js_name = removeChild)]
     pub fn remove_child(&mut self, child: &YogaNode) {
-        let _ = self.taffy.remove_child(self.node, child.node);
+        if !Rc::ptr_eq(&self.tree, &child.tree) {
+            return;
+        }
+        let mut tree = self.tree.borrow_mut();
+        let _ = tree.taffy.remove_child(self.node, child.node);
     }
 
-    /// Get the number of children.
+    /// Get the number of children, counting the hoisted children of any
+    /// `display: contents` child in place of the wrapper itself.
     #[wasm_bindgen(js_name = getChildCount)]
     pub fn get_child_count(&self) -> u32 {
-        self.taffy.children(self.node).map(|children| children.len()).unwrap_or(0) as u32
+        let tree = self.tree.borrow();
+        flatten_contents_children(&tree.taffy, &tree.contents_nodes, self.node).len() as u32
     }
 
     // ==========================================================================
@@ -57,261 +199,369 @@ impl YogaNode {
 
     /// Set width in points.
     #[wasm_bindgen(js_name = setWidth)]
-    pub fn set_width(&mut self, width: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.size.width = length(width);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_width(&self, width: f32) {
+        self.with_style_mut(|style| style.size.width = length(width));
+    }
+
+    /// Set width as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setWidthPercent)]
+    pub fn set_width_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.size.width = percent_dimension(percent));
+    }
+
+    /// Set width to auto (sized by content/flex rules).
+    #[wasm_bindgen(js_name = setWidthAuto)]
+    pub fn set_width_auto(&self) {
+        self.with_style_mut(|style| style.size.width = Dimension::Auto);
     }
 
     /// Set height in points.
     #[wasm_bindgen(js_name = setHeight)]
-    pub fn set_height(&mut self, height: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.size.height = length(height);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_height(&self, height: f32) {
+        self.with_style_mut(|style| style.size.height = length(height));
+    }
+
+    /// Set height as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setHeightPercent)]
+    pub fn set_height_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.size.height = percent_dimension(percent));
+    }
+
+    /// Set height to auto (sized by content/flex rules).
+    #[wasm_bindgen(js_name = setHeightAuto)]
+    pub fn set_height_auto(&self) {
+        self.with_style_mut(|style| style.size.height = Dimension::Auto);
     }
 
     /// Set min width in points.
     #[wasm_bindgen(js_name = setMinWidth)]
-    pub fn set_min_width(&mut self, min_width: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.min_size.width = length(min_width);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_min_width(&self, min_width: f32) {
+        self.with_style_mut(|style| style.min_size.width = length(min_width));
+    }
+
+    /// Set min width as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setMinWidthPercent)]
+    pub fn set_min_width_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.min_size.width = percent_dimension(percent));
     }
 
     /// Set min height in points.
     #[wasm_bindgen(js_name = setMinHeight)]
-    pub fn set_min_height(&mut self, min_height: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.min_size.height = length(min_height);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_min_height(&self, min_height: f32) {
+        self.with_style_mut(|style| style.min_size.height = length(min_height));
+    }
+
+    /// Set min height as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setMinHeightPercent)]
+    pub fn set_min_height_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.min_size.height = percent_dimension(percent));
     }
 
     /// Set max width in points.
     #[wasm_bindgen(js_name = setMaxWidth)]
-    pub fn set_max_width(&mut self, max_width: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.max_size.width = length(max_width);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_max_width(&self, max_width: f32) {
+        self.with_style_mut(|style| style.max_size.width = length(max_width));
+    }
+
+    /// Set max width as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setMaxWidthPercent)]
+    pub fn set_max_width_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.max_size.width = percent_dimension(percent));
     }
 
     /// Set max height in points.
     #[wasm_bindgen(js_name = setMaxHeight)]
-    pub fn set_max_height(&mut self, max_height: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.max_size.height = length(max_height);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_max_height(&self, max_height: f32) {
+        self.with_style_mut(|style| style.max_size.height = length(max_height));
     }
 
-    /// Set flex direction (0=Column, 1=ColumnReverse, 2=Row, 3=RowReverse).
+    /// Set max height as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setMaxHeightPercent)]
+    pub fn set_max_height_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.max_size.height = percent_dimension(percent));
+    }
+
+    /// Set flex direction.
     #[wasm_bindgen(js_name = setFlexDirection)]
-    pub fn set_flex_direction(&mut self, direction: u32) {
-        let flex_dir = match direction {
-            0 => FlexDirection::Column,
-            1 => FlexDirection::ColumnReverse,
-            2 => FlexDirection::Row,
-            3 => FlexDirection::RowReverse,
-            _ => FlexDirection::Column,
+    pub fn set_flex_direction(&self, direction: YgFlexDirection) {
+        let flex_direction = match direction {
+            YgFlexDirection::Column => FlexDirection::Column,
+            YgFlexDirection::ColumnReverse => FlexDirection::ColumnReverse,
+            YgFlexDirection::Row => FlexDirection::Row,
+            YgFlexDirection::RowReverse => FlexDirection::RowReverse,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_direction = flex_dir;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.flex_direction = flex_direction);
     }
 
-    /// Set justify content (0=FlexStart, 1=Center, 2=FlexEnd, 3=SpaceBetween, 4=SpaceAround, 5=SpaceEvenly).
+    /// Set justify content.
     #[wasm_bindgen(js_name = setJustifyContent)]
-    pub fn set_justify_content(&mut self, justify: u32) {
+    pub fn set_justify_content(&self, justify: YgJustifyContent) {
         let justify_content = match justify {
-            0 => Some(JustifyContent::Start),
-            1 => Some(JustifyContent::Center),
-            2 => Some(JustifyContent::End),
-            3 => Some(JustifyContent::SpaceBetween),
-            4 => Some(JustifyContent::SpaceAround),
-            5 => Some(JustifyContent::SpaceEvenly),
-            _ => Some(JustifyContent::Start),
+            YgJustifyContent::FlexStart => JustifyContent::Start,
+            YgJustifyContent::Center => JustifyContent::Center,
+            YgJustifyContent::FlexEnd => JustifyContent::End,
+            YgJustifyContent::SpaceBetween => JustifyContent::SpaceBetween,
+            YgJustifyContent::SpaceAround => JustifyContent::SpaceAround,
+            YgJustifyContent::SpaceEvenly => JustifyContent::SpaceEvenly,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.justify_content = justify_content;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.justify_content = Some(justify_content));
     }
 
-    /// Set align items (0=Auto, 1=FlexStart, 2=Center, 3=FlexEnd, 4=Stretch, 5=Baseline, 6=SpaceBetween, 7=SpaceAround).
+    /// Set align items.
     #[wasm_bindgen(js_name = setAlignItems)]
-    pub fn set_align_items(&mut self, align: u32) {
+    pub fn set_align_items(&self, align: YgAlign) {
         let align_items = match align {
-            0 => Some(AlignItems::Start), // Auto → Start
-            1 => Some(AlignItems::Start),
-            2 => Some(AlignItems::Center),
-            3 => Some(AlignItems::End),
-            4 => Some(AlignItems::Stretch),
-            5 => Some(AlignItems::Baseline),
-            6 => Some(AlignItems::Start), // SpaceBetween not in AlignItems
-            7 => Some(AlignItems::Start), // SpaceAround not in AlignItems
-            _ => Some(AlignItems::Start),
+            // `AlignItems` has no SpaceBetween/SpaceAround; Yoga folds those to Start too.
+            YgAlign::Auto | YgAlign::FlexStart | YgAlign::SpaceBetween | YgAlign::SpaceAround => {
+                AlignItems::Start
+            }
+            YgAlign::Center => AlignItems::Center,
+            YgAlign::FlexEnd => AlignItems::End,
+            YgAlign::Stretch => AlignItems::Stretch,
+            YgAlign::Baseline => AlignItems::Baseline,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.align_items = align_items;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.align_items = Some(align_items));
     }
 
-    /// Set align content (0=Auto, 1=FlexStart, 2=Center, 3=FlexEnd, 4=Stretch, 5=Baseline, 6=SpaceBetween, 7=SpaceAround).
+    /// Set align content.
     #[wasm_bindgen(js_name = setAlignContent)]
-    pub fn set_align_content(&mut self, align: u32) {
+    pub fn set_align_content(&self, align: YgAlign) {
         let align_content = match align {
-            0 => Some(AlignContent::Start), // Auto → Start
-            1 => Some(AlignContent::Start),
-            2 => Some(AlignContent::Center),
-            3 => Some(AlignContent::End),
-            4 => Some(AlignContent::Stretch),
-            5 => Some(AlignContent::Start), // Baseline not in AlignContent
-            6 => Some(AlignContent::SpaceBetween),
-            7 => Some(AlignContent::SpaceAround),
-            _ => Some(AlignContent::Start),
+            YgAlign::Auto | YgAlign::FlexStart => AlignContent::Start,
+            YgAlign::Center => AlignContent::Center,
+            YgAlign::FlexEnd => AlignContent::End,
+            YgAlign::Stretch => AlignContent::Stretch,
+            // `AlignContent` has no Baseline; Yoga folds it to Start.
+            YgAlign::Baseline => AlignContent::Start,
+            YgAlign::SpaceBetween => AlignContent::SpaceBetween,
+            YgAlign::SpaceAround => AlignContent::SpaceAround,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.align_content = align_content;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.align_content = Some(align_content));
     }
 
-    /// Set align self (0=Auto, 1=FlexStart, 2=Center, 3=FlexEnd, 4=Stretch, 5=Baseline, 6=SpaceBetween, 7=SpaceAround).
+    /// Set align self.
     #[wasm_bindgen(js_name = setAlignSelf)]
-    pub fn set_align_self(&mut self, align: u32) {
+    pub fn set_align_self(&self, align: YgAlign) {
         let align_self = match align {
-            0 => Some(AlignSelf::Start), // Auto → Start
-            1 => Some(AlignSelf::Start),
-            2 => Some(AlignSelf::Center),
-            3 => Some(AlignSelf::End),
-            4 => Some(AlignSelf::Stretch),
-            5 => Some(AlignSelf::Baseline),
-            6 => Some(AlignSelf::Start), // SpaceBetween not in AlignSelf
-            7 => Some(AlignSelf::Start), // SpaceAround not in AlignSelf
-            _ => Some(AlignSelf::Start),
+            // `AlignSelf` has no SpaceBetween/SpaceAround; those fold to Start.
+            YgAlign::Auto | YgAlign::FlexStart | YgAlign::SpaceBetween | YgAlign::SpaceAround => {
+                AlignSelf::Start
+            }
+            YgAlign::Center => AlignSelf::Center,
+            YgAlign::FlexEnd => AlignSelf::End,
+            YgAlign::Stretch => AlignSelf::Stretch,
+            YgAlign::Baseline => AlignSelf::Baseline,
+        };
+        self.with_style_mut(|style| style.align_self = Some(align_self));
+    }
+
+    /// Set display mode.
+    ///
+    /// `None` removes the node from layout entirely (zero size, children not
+    /// laid out). `Grid` routes this node's children through Taffy's CSS Grid
+    /// algorithm instead of flexbox.
+    ///
+    /// Taffy has no engine-level equivalent of `display: contents`, so
+    /// `Contents` isn't stored in the node's `Style` at all (there is no such
+    /// `taffy::style::Display` variant to store it in). Instead the node is
+    /// tracked in `TreeState::contents_nodes`, and `calculate_layout`
+    /// temporarily reparents its children onto its own parent — bypassing it
+    /// entirely — before invoking Taffy's layout algorithm, and restores the
+    /// real tree shape afterward. See `apply_contents_flattening`.
+    #[wasm_bindgen(js_name = setDisplay)]
+    pub fn set_display(&self, mode: YgDisplay) {
+        if matches!(mode, YgDisplay::Contents) {
+            self.tree.borrow_mut().contents_nodes.insert(self.node);
+            return;
+        }
+        self.tree.borrow_mut().contents_nodes.remove(&self.node);
+        let display = match mode {
+            YgDisplay::Flex => Display::Flex,
+            YgDisplay::None => Display::None,
+            YgDisplay::Grid => Display::Grid,
+            YgDisplay::Contents => unreachable!(),
+        };
+        self.with_style_mut(|style| style.display = display);
+    }
+
+    // ==========================================================================
+    // CSS Grid
+    // ==========================================================================
+
+    /// Set `grid-template-columns` from a space-separated track list, e.g.
+    /// `"100px 1fr auto min-content max-content"`.
+    #[wasm_bindgen(js_name = setGridTemplateColumns)]
+    pub fn set_grid_template_columns(&self, tracks: &str) {
+        let tracks = parse_track_list(tracks);
+        self.with_style_mut(|style| style.grid_template_columns = tracks);
+    }
+
+    /// Set `grid-template-rows` from a space-separated track list (see
+    /// `setGridTemplateColumns` for the accepted syntax).
+    #[wasm_bindgen(js_name = setGridTemplateRows)]
+    pub fn set_grid_template_rows(&self, tracks: &str) {
+        let tracks = parse_track_list(tracks);
+        self.with_style_mut(|style| style.grid_template_rows = tracks);
+    }
+
+    /// Set the track list used to size implicit rows created by
+    /// auto-placement, e.g. `"min-content 2fr"`.
+    #[wasm_bindgen(js_name = setGridAutoRows)]
+    pub fn set_grid_auto_rows(&self, tracks: &str) {
+        let tracks = parse_non_repeated_track_list(tracks);
+        self.with_style_mut(|style| style.grid_auto_rows = tracks);
+    }
+
+    /// Set the track list used to size implicit columns created by
+    /// auto-placement (see `setGridAutoRows`).
+    #[wasm_bindgen(js_name = setGridAutoColumns)]
+    pub fn set_grid_auto_columns(&self, tracks: &str) {
+        let tracks = parse_non_repeated_track_list(tracks);
+        self.with_style_mut(|style| style.grid_auto_columns = tracks);
+    }
+
+    /// Set auto-placement flow.
+    #[wasm_bindgen(js_name = setGridAutoFlow)]
+    pub fn set_grid_auto_flow(&self, flow: YgGridAutoFlow) {
+        let grid_auto_flow = match flow {
+            YgGridAutoFlow::Row => GridAutoFlow::Row,
+            YgGridAutoFlow::Column => GridAutoFlow::Column,
+            YgGridAutoFlow::RowDense => GridAutoFlow::RowDense,
+            YgGridAutoFlow::ColumnDense => GridAutoFlow::ColumnDense,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.align_self = align_self;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.grid_auto_flow = grid_auto_flow);
+    }
+
+    /// Place this node on the row axis. `0` means auto-placed, a positive
+    /// value is a 1-based line number, and a negative value `-N` means
+    /// "span N tracks" for that edge.
+    #[wasm_bindgen(js_name = setGridRow)]
+    pub fn set_grid_row(&self, start: i32, end: i32) {
+        let line = Line { start: grid_placement(start), end: grid_placement(end) };
+        self.with_style_mut(|style| style.grid_row = line);
+    }
+
+    /// Place this node on the column axis (see `setGridRow` for the
+    /// start/end/span encoding).
+    #[wasm_bindgen(js_name = setGridColumn)]
+    pub fn set_grid_column(&self, start: i32, end: i32) {
+        let line = Line { start: grid_placement(start), end: grid_placement(end) };
+        self.with_style_mut(|style| style.grid_column = line);
     }
 
-    /// Set flex wrap (0=NoWrap, 1=Wrap, 2=WrapReverse).
+    /// Set flex wrap.
     #[wasm_bindgen(js_name = setFlexWrap)]
-    pub fn set_flex_wrap(&mut self, wrap: u32) {
+    pub fn set_flex_wrap(&self, wrap: YgFlexWrap) {
         let flex_wrap = match wrap {
-            0 => FlexWrap::NoWrap,
-            1 => FlexWrap::Wrap,
-            2 => FlexWrap::WrapReverse,
-            _ => FlexWrap::NoWrap,
+            YgFlexWrap::NoWrap => FlexWrap::NoWrap,
+            YgFlexWrap::Wrap => FlexWrap::Wrap,
+            YgFlexWrap::WrapReverse => FlexWrap::WrapReverse,
         };
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_wrap = flex_wrap;
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| style.flex_wrap = flex_wrap);
     }
 
     /// Set flex shorthand property.
     #[wasm_bindgen(js_name = setFlex)]
-    pub fn set_flex(&mut self, flex: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_grow = flex;
-        style.flex_shrink = 1.0;
-        style.flex_basis = length(0.0);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_flex(&self, flex: f32) {
+        self.with_style_mut(|style| {
+            style.flex_grow = flex;
+            style.flex_shrink = 1.0;
+            style.flex_basis = length(0.0);
+        });
     }
 
     /// Set flex grow.
     #[wasm_bindgen(js_name = setFlexGrow)]
-    pub fn set_flex_grow(&mut self, flex_grow: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_grow = flex_grow;
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_flex_grow(&self, flex_grow: f32) {
+        self.with_style_mut(|style| style.flex_grow = flex_grow);
     }
 
     /// Set flex shrink.
     #[wasm_bindgen(js_name = setFlexShrink)]
-    pub fn set_flex_shrink(&mut self, flex_shrink: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_shrink = flex_shrink;
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_flex_shrink(&self, flex_shrink: f32) {
+        self.with_style_mut(|style| style.flex_shrink = flex_shrink);
     }
 
     /// Set flex basis in points.
     #[wasm_bindgen(js_name = setFlexBasis)]
-    pub fn set_flex_basis(&mut self, flex_basis: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
-        style.flex_basis = length(flex_basis);
-        let _ = self.taffy.set_style(self.node, style);
+    pub fn set_flex_basis(&self, flex_basis: f32) {
+        self.with_style_mut(|style| style.flex_basis = length(flex_basis));
+    }
+
+    /// Set flex basis as a percentage of the containing block (0-100).
+    #[wasm_bindgen(js_name = setFlexBasisPercent)]
+    pub fn set_flex_basis_percent(&self, percent: f32) {
+        self.with_style_mut(|style| style.flex_basis = percent_dimension(percent));
+    }
+
+    /// Set aspect ratio (width / height). Pass `NaN` to unset.
+    ///
+    /// When only one of width/height is definite, Taffy derives the other from
+    /// this ratio (clamped against min/max size); an explicit width and height
+    /// both being set overrides the ratio.
+    #[wasm_bindgen(js_name = setAspectRatio)]
+    pub fn set_aspect_ratio(&self, ratio: f32) {
+        self.with_style_mut(|style| {
+            style.aspect_ratio = if ratio.is_nan() { None } else { Some(ratio) };
+        });
     }
 
-    // Padding (edge: 0=Left, 1=Top, 2=Right, 3=Bottom, 4=Start, 5=End, 6=Horizontal, 7=Vertical, 8=All).
     /// Set padding for a specific edge.
     #[wasm_bindgen(js_name = setPadding)]
-    pub fn set_padding(&mut self, edge: u32, padding: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
+    pub fn set_padding(&self, edge: YgEdge, padding: f32) {
         let pad = length_pct(padding);
-        match edge {
-            0 => style.padding.left = pad,   // Left
-            1 => style.padding.top = pad,    // Top
-            2 => style.padding.right = pad,  // Right
-            3 => style.padding.bottom = pad, // Bottom
-            4 => style.padding.left = pad,   // Start → Left
-            5 => style.padding.right = pad,  // End → Right
-            6 => {
-                // Horizontal
-                style.padding.left = pad;
-                style.padding.right = pad;
-            }
-            7 => {
-                // Vertical
-                style.padding.top = pad;
-                style.padding.bottom = pad;
-            }
-            8 => {
-                // All
-                style.padding = Rect {
-                    left: pad,
-                    right: pad,
-                    top: pad,
-                    bottom: pad,
-                };
-            }
-            _ => {}
-        }
-        let _ = self.taffy.set_style(self.node, style);
+        self.with_style_mut(|style| set_edge_rect(&mut style.padding, edge, pad));
     }
 
-    // Margin (edge: 0=Left, 1=Top, 2=Right, 3=Bottom, 4=Start, 5=End, 6=Horizontal, 7=Vertical, 8=All).
     /// Set margin for a specific edge.
     #[wasm_bindgen(js_name = setMargin)]
-    pub fn set_margin(&mut self, edge: u32, margin: f32) {
-        let mut style = self.taffy.style(self.node).unwrap().clone();
+    pub fn set_margin(&self, edge: YgEdge, margin: f32) {
         let mar = length_auto(margin);
-        match edge {
-            0 => style.margin.left = mar,   // Left
-            1 => style.margin.top = mar,    // Top
-            2 => style.margin.right = mar,  // Right
-            3 => style.margin.bottom = mar, // Bottom
-            4 => style.margin.left = mar,   // Start → Left
-            5 => style.margin.right = mar,  // End → Right
-            6 => {
-                // Horizontal
-                style.margin.left = mar;
-                style.margin.right = mar;
-            }
-            7 => {
-                // Vertical
-                style.margin.top = mar;
-                style.margin.bottom = mar;
-            }
-            8 => {
-                // All
-                style.margin = Rect {
-                    left: mar,
-                    right: mar,
-                    top: mar,
-                    bottom: mar,
-                };
+        self.with_style_mut(|style| set_edge_rect(&mut style.margin, edge, mar));
+    }
+
+    // ==========================================================================
+    // Gap and absolute positioning
+    // ==========================================================================
+
+    /// Set gap between flex/grid items in points.
+    #[wasm_bindgen(js_name = setGap)]
+    pub fn set_gap(&self, gutter: YgGutter, value: f32) {
+        let gap = length_pct(value);
+        self.with_style_mut(|style| match gutter {
+            YgGutter::Column => style.gap.width = gap,
+            YgGutter::Row => style.gap.height = gap,
+            YgGutter::All => {
+                style.gap.width = gap;
+                style.gap.height = gap;
             }
-            _ => {}
-        }
-        let _ = self.taffy.set_style(self.node, style);
+        });
+    }
+
+    /// Set position type. Absolute nodes are removed from the flex/grid flow
+    /// and positioned by `setPosition` against the containing block.
+    #[wasm_bindgen(js_name = setPositionType)]
+    pub fn set_position_type(&self, position_type: YgPositionType) {
+        let position = match position_type {
+            YgPositionType::Relative => Position::Relative,
+            YgPositionType::Absolute => Position::Absolute,
+        };
+        self.with_style_mut(|style| style.position = position);
+    }
+
+    /// Set inset for a specific edge, in points.
+    #[wasm_bindgen(js_name = setPosition)]
+    pub fn set_position(&self, edge: YgEdge, value: f32) {
+        let inset = length_auto(value);
+        self.with_style_mut(|style| set_edge_rect(&mut style.inset, edge, inset));
+    }
+
+    /// Set inset for a specific edge, as a percentage of the containing
+    /// block (0-100).
+    #[wasm_bindgen(js_name = setPositionPercent)]
+    pub fn set_position_percent(&self, edge: YgEdge, percent: f32) {
+        let inset = LengthPercentageAuto::Percent(percent / 100.0);
+        self.with_style_mut(|style| set_edge_rect(&mut style.inset, edge, inset));
     }
 
     // ==========================================================================
@@ -319,13 +569,59 @@ impl YogaNode {
     // ==========================================================================
 
     /// Calculate layout with specified width and height.
+    ///
+    /// Routes through Taffy's measure-aware pass so any node with a
+    /// registered `setMeasureFunc` callback (e.g. a text leaf) gets sized by
+    /// calling into JS instead of relying on a fixed/flex dimension.
+    ///
+    /// Before computing layout, any `display: contents` node in this
+    /// subtree is temporarily removed from the real Taffy tree and its
+    /// children spliced into its parent's child list (see
+    /// `apply_contents_flattening`), so those children are laid out as
+    /// participants of the *grandparent's* flex/grid line, not the wrapper's
+    /// own (otherwise-default) container. The real tree shape is restored
+    /// afterward, so `insertChild`/`getChild`/etc. keep seeing the logical
+    /// (unflattened) structure.
     #[wasm_bindgen(js_name = calculateLayout)]
-    pub fn calculate_layout(&mut self, width: f32, height: f32) {
+    pub fn calculate_layout(&self, width: f32, height: f32) {
         let available_space = Size {
             width: AvailableSpace::Definite(width),
             height: AvailableSpace::Definite(height),
         };
-        let _ = self.taffy.compute_layout(self.node, available_space);
+        let mut tree = self.tree.borrow_mut();
+        let TreeState { taffy, measure_funcs, contents_nodes } = &mut *tree;
+        let rewritten = apply_contents_flattening(taffy, contents_nodes, self.node);
+        let _ = taffy.compute_layout_with_measure(
+            self.node,
+            available_space,
+            |known_dimensions, available_space, node_id, _node_context, _style| {
+                let Some(cb) = measure_funcs.get(&node_id) else {
+                    return Size::ZERO;
+                };
+                invoke_measure_func(cb, known_dimensions, available_space)
+            },
+        );
+        restore_contents_flattening(taffy, rewritten);
+    }
+
+    /// Register a JS callback invoked during layout to size this node's
+    /// content (e.g. wrapped text). The callback receives
+    /// `(knownWidth, knownHeight, availableWidth, availableHeight)` — unknown
+    /// dimensions are `NaN` and MinContent/MaxContent available space is
+    /// represented by `-Infinity`/`Infinity` — and must return `{width, height}`.
+    #[wasm_bindgen(js_name = setMeasureFunc)]
+    pub fn set_measure_func(&self, cb: Function) {
+        let mut tree = self.tree.borrow_mut();
+        tree.measure_funcs.insert(self.node, cb);
+        let _ = tree.taffy.mark_dirty(self.node);
+    }
+
+    /// Remove this node's measure callback, reverting it to fixed/flex sizing.
+    #[wasm_bindgen(js_name = unsetMeasureFunc)]
+    pub fn unset_measure_func(&self) {
+        let mut tree = self.tree.borrow_mut();
+        tree.measure_funcs.remove(&self.node);
+        let _ = tree.taffy.mark_dirty(self.node);
     }
 
     // ==========================================================================
@@ -335,43 +631,33 @@ impl YogaNode {
     /// Get computed left position.
     #[wasm_bindgen(js_name = getComputedLeft)]
     pub fn get_computed_left(&self) -> f32 {
-        self.taffy
-            .layout(self.node)
-            .map(|l| l.location.x)
-            .unwrap_or(0.0)
+        self.tree.borrow().taffy.layout(self.node).map(|l| l.location.x).unwrap_or(0.0)
     }
 
     /// Get computed top position.
     #[wasm_bindgen(js_name = getComputedTop)]
     pub fn get_computed_top(&self) -> f32 {
-        self.taffy
-            .layout(self.node)
-            .map(|l| l.location.y)
-            .unwrap_or(0.0)
+        self.tree.borrow().taffy.layout(self.node).map(|l| l.location.y).unwrap_or(0.0)
     }
 
     /// Get computed width.
     #[wasm_bindgen(js_name = getComputedWidth)]
     pub fn get_computed_width(&self) -> f32 {
-        self.taffy
-            .layout(self.node)
-            .map(|l| l.size.width)
-            .unwrap_or(0.0)
+        self.tree.borrow().taffy.layout(self.node).map(|l| l.size.width).unwrap_or(0.0)
     }
 
     /// Get computed height.
     #[wasm_bindgen(js_name = getComputedHeight)]
     pub fn get_computed_height(&self) -> f32 {
-        self.taffy
-            .layout(self.node)
-            .map(|l| l.size.height)
-            .unwrap_or(0.0)
+        self.tree.borrow().taffy.layout(self.node).map(|l| l.size.height).unwrap_or(0.0)
     }
 
     /// Get computed right position.
     #[wasm_bindgen(js_name = getComputedRight)]
     pub fn get_computed_right(&self) -> f32 {
-        self.taffy
+        self.tree
+            .borrow()
+            .taffy
             .layout(self.node)
             .map(|l| l.location.x + l.size.width)
             .unwrap_or(0.0)
@@ -380,7 +666,9 @@ impl YogaNode {
     /// Get computed bottom position.
     #[wasm_bindgen(js_name = getComputedBottom)]
     pub fn get_computed_bottom(&self) -> f32 {
-        self.taffy
+        self.tree
+            .borrow()
+            .taffy
             .layout(self.node)
             .map(|l| l.location.y + l.size.height)
             .unwrap_or(0.0)
@@ -390,12 +678,25 @@ impl YogaNode {
     // Node hierarchy
     // ==========================================================================
 
-    /// Get child at index.
+    /// Get child at index (accounting for hoisted `display: contents`
+    /// children, see `getChildCount`), backed by a real tree query.
     #[wasm_bindgen(js_name = getChild)]
-    pub fn get_child(&self, _index: u32) -> Option<YogaNode> {
-        // Note: wasm-bindgen doesn't support returning complex types easily.
-        // For now, return None. Full implementation would need to track nodes in a registry.
-        None
+    pub fn get_child(&self, index: u32) -> Option<YogaNode> {
+        let children = {
+            let tree = self.tree.borrow();
+            flatten_contents_children(&tree.taffy, &tree.contents_nodes, self.node)
+        };
+        children.get(index as usize).map(|&node| YogaNode { tree: Rc::clone(&self.tree), node })
+    }
+
+    /// Get this node's parent, if any.
+    #[wasm_bindgen(js_name = getParent)]
+    pub fn get_parent(&self) -> Option<YogaNode> {
+        self.tree
+            .borrow()
+            .taffy
+            .parent(self.node)
+            .map(|node| YogaNode { tree: Rc::clone(&self.tree), node })
     }
 
     // ==========================================================================
@@ -416,8 +717,82 @@ impl YogaNode {
 
     /// Reset node to default style.
     #[wasm_bindgen]
-    pub fn reset(&mut self) {
-        let _ = self.taffy.set_style(self.node, Style::default());
+    pub fn reset(&self) {
+        let mut tree = self.tree.borrow_mut();
+        tree.contents_nodes.remove(&self.node);
+        let _ = tree.taffy.set_style(self.node, Style::default());
+    }
+}
+
+// Internal helpers not exposed to JS, kept out of the `#[wasm_bindgen]` impl
+// block above.
+impl YogaNode {
+    // Fetch this node's style, apply `f`, and write it back in one step —
+    // the common read-modify-write pattern behind every style setter.
+    fn with_style_mut(&self, f: impl FnOnce(&mut Style)) {
+        let mut tree = self.tree.borrow_mut();
+        if let Ok(style) = tree.taffy.style(self.node) {
+            let mut style = style.clone();
+            f(&mut style);
+            let _ = tree.taffy.set_style(self.node, style);
+        }
+    }
+
+    // Ensure `child` shares this node's tree, cloning its subtree into this
+    // tree and repointing `child` at the clone if it didn't already.
+    fn adopt(&mut self, child: &mut YogaNode) -> NodeId {
+        if Rc::ptr_eq(&self.tree, &child.tree) {
+            return child.node;
+        }
+        let new_node = {
+            let mut dest = self.tree.borrow_mut();
+            let src = child.tree.borrow();
+            clone_subtree(&mut dest, &src, child.node)
+        };
+        child.tree = Rc::clone(&self.tree);
+        child.node = new_node;
+        new_node
+    }
+}
+
+// Recursively copy `src_node` (style, measure func, contents-node membership,
+// and children) from `src` into `dest`, returning the new node's id in `dest`.
+fn clone_subtree(dest: &mut TreeState, src: &TreeState, src_node: NodeId) -> NodeId {
+    let style = src.taffy.style(src_node).cloned().unwrap_or_default();
+    let dest_node = dest.taffy.new_leaf(style).unwrap();
+    if let Some(cb) = src.measure_funcs.get(&src_node) {
+        dest.measure_funcs.insert(dest_node, cb.clone());
+    }
+    if src.contents_nodes.contains(&src_node) {
+        dest.contents_nodes.insert(dest_node);
+    }
+    if let Ok(children) = src.taffy.children(src_node) {
+        for child in children {
+            let dest_child = clone_subtree(dest, src, child);
+            let _ = dest.taffy.add_child(dest_node, dest_child);
+        }
+    }
+    dest_node
+}
+
+// Write `value` into the edge(s) of a padding/margin/inset rect selected by `edge`.
+fn set_edge_rect<T: Copy>(rect: &mut Rect<T>, edge: YgEdge, value: T) {
+    match edge {
+        YgEdge::Left => rect.left = value,
+        YgEdge::Top => rect.top = value,
+        YgEdge::Right => rect.right = value,
+        YgEdge::Bottom => rect.bottom = value,
+        YgEdge::Start => rect.left = value,
+        YgEdge::End => rect.right = value,
+        YgEdge::Horizontal => {
+            rect.left = value;
+            rect.right = value;
+        }
+        YgEdge::Vertical => {
+            rect.top = value;
+            rect.bottom = value;
+        }
+        YgEdge::All => *rect = Rect { left: value, right: value, top: value, bottom: value },
     }
 }
 
@@ -426,6 +801,72 @@ fn length(value: f32) -> Dimension {
     Dimension::Length(value)
 }
 
+// Helper function to create a percentage dimension from a 0-100 value.
+fn percent_dimension(percent: f32) -> Dimension {
+    Dimension::Percent(percent / 100.0)
+}
+
+// Parse a single grid track token: a pixel length ("100px" or "100"), an "Nfr"
+// fraction, or one of the `auto`/`min-content`/`max-content` keywords.
+fn parse_track_size(token: &str) -> NonRepeatedTrackSizingFunction {
+    let token = token.trim();
+    match token {
+        "auto" => NonRepeatedTrackSizingFunction {
+            min: MinTrackSizingFunction::Auto,
+            max: MaxTrackSizingFunction::Auto,
+        },
+        "min-content" => NonRepeatedTrackSizingFunction {
+            min: MinTrackSizingFunction::MinContent,
+            max: MaxTrackSizingFunction::MinContent,
+        },
+        "max-content" => NonRepeatedTrackSizingFunction {
+            min: MinTrackSizingFunction::MaxContent,
+            max: MaxTrackSizingFunction::MaxContent,
+        },
+        _ if token.ends_with("fr") => {
+            let value: f32 = token.trim_end_matches("fr").trim().parse().unwrap_or(1.0);
+            NonRepeatedTrackSizingFunction {
+                min: MinTrackSizingFunction::Auto,
+                max: MaxTrackSizingFunction::Fraction(value),
+            }
+        }
+        _ => {
+            let value: f32 = token.trim_end_matches("px").trim().parse().unwrap_or(0.0);
+            let fixed = LengthPercentage::Length(value);
+            NonRepeatedTrackSizingFunction {
+                min: MinTrackSizingFunction::Fixed(fixed),
+                max: MaxTrackSizingFunction::Fixed(fixed),
+            }
+        }
+    }
+}
+
+// Parse a space-separated track list for `grid-template-columns`/`-rows`.
+fn parse_track_list(tracks: &str) -> Vec<TrackSizingFunction> {
+    tracks
+        .split_whitespace()
+        .map(|token| TrackSizingFunction::Single(parse_track_size(token)))
+        .collect()
+}
+
+// Parse a space-separated track list for `grid-auto-rows`/`-columns`, which
+// (unlike the template lists) cannot contain `repeat()` groups.
+fn parse_non_repeated_track_list(tracks: &str) -> Vec<NonRepeatedTrackSizingFunction> {
+    tracks.split_whitespace().map(parse_track_size).collect()
+}
+
+// Decode a line placement integer: 0 is auto, negative `-N` is `span N`,
+// otherwise it's a 1-based grid line number.
+fn grid_placement(value: i32) -> GridPlacement {
+    if value == 0 {
+        GridPlacement::Auto
+    } else if value < 0 {
+        GridPlacement::Span((-value) as u16)
+    } else {
+        GridPlacement::Line((value as i16).into())
+    }
+}
+
 // Helper function to create a length percentage (for padding).
 fn length_pct(value: f32) -> LengthPercentage {
     LengthPercentage::Length(value)
@@ -435,3 +876,130 @@ fn length_pct(value: f32) -> LengthPercentage {
 fn length_auto(value: f32) -> LengthPercentageAuto {
     LengthPercentageAuto::Length(value)
 }
+
+// Call a registered measure callback and parse its `{width, height}` result.
+fn invoke_measure_func(
+    cb: &Function,
+    known_dimensions: Size<Option<f32>>,
+    available_space: Size<AvailableSpace>,
+) -> Size<f32> {
+    let args = Array::new();
+    args.push(&JsValue::from(known_dimensions.width.unwrap_or(f32::NAN)));
+    args.push(&JsValue::from(known_dimensions.height.unwrap_or(f32::NAN)));
+    args.push(&available_space_to_js(available_space.width));
+    args.push(&available_space_to_js(available_space.height));
+
+    let Ok(result) = cb.apply(&JsValue::undefined(), &args) else {
+        return Size::ZERO;
+    };
+    let width = Reflect::get(&result, &JsValue::from_str("width"))
+        .ok()
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0) as f32;
+    let height = Reflect::get(&result, &JsValue::from_str("height"))
+        .ok()
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0) as f32;
+    Size { width, height }
+}
+
+// Encode available space as a single JS number: a definite value passes
+// through, MinContent/MaxContent become -Infinity/Infinity sentinels.
+fn available_space_to_js(space: AvailableSpace) -> JsValue {
+    match space {
+        AvailableSpace::Definite(value) => JsValue::from(value),
+        AvailableSpace::MinContent => JsValue::from(AVAILABLE_SPACE_MIN_CONTENT),
+        AvailableSpace::MaxContent => JsValue::from(AVAILABLE_SPACE_MAX_CONTENT),
+    }
+}
+
+// Gather `node`'s effective flex/grid children, recursively replacing any
+// `display: contents` child with its own children (transitively), so a
+// wrapper node never contributes itself as a layout participant. Used by the
+// tree-navigation getters (`getChild`/`getChildCount`); `calculate_layout`
+// uses `apply_contents_flattening` instead, which performs the same
+// substitution on the real Taffy tree so the layout algorithm sees it too.
+fn flatten_contents_children(
+    taffy: &TaffyTree,
+    contents_nodes: &HashSet<NodeId>,
+    node: NodeId,
+) -> Vec<NodeId> {
+    let mut result = Vec::new();
+    let Ok(children) = taffy.children(node) else {
+        return result;
+    };
+    for child in children {
+        if contents_nodes.contains(&child) {
+            result.extend(flatten_contents_children(taffy, contents_nodes, child));
+        } else {
+            result.push(child);
+        }
+    }
+    result
+}
+
+// Temporarily rewrite the real Taffy tree so every `display: contents` node
+// is spliced out of its parent's child list and replaced by its own
+// (recursively flattened) children, then return what was rewritten so
+// `restore_contents_flattening` can put the logical tree back afterward.
+// Without this, Taffy's own flex/grid algorithm would lay a contents node's
+// children out as participants of *that node's* container (whatever default
+// style it has) instead of hoisting them into the grandparent's line.
+fn apply_contents_flattening(
+    taffy: &mut TaffyTree,
+    contents_nodes: &HashSet<NodeId>,
+    root: NodeId,
+) -> Vec<(NodeId, Vec<NodeId>)> {
+    let mut rewritten = Vec::new();
+    flatten_node_children(taffy, contents_nodes, root, &mut rewritten);
+    rewritten
+}
+
+// Post-order: flatten every descendant's child list first, so that by the
+// time we compute `node`'s effective children, any contents child's own
+// children are already fully (transitively) flattened.
+fn flatten_node_children(
+    taffy: &mut TaffyTree,
+    contents_nodes: &HashSet<NodeId>,
+    node: NodeId,
+    rewritten: &mut Vec<(NodeId, Vec<NodeId>)>,
+) {
+    let Ok(original_children) = taffy.children(node) else {
+        return;
+    };
+    for &child in &original_children {
+        flatten_node_children(taffy, contents_nodes, child, rewritten);
+    }
+    let effective: Vec<NodeId> = original_children
+        .iter()
+        .flat_map(|&child| {
+            if contents_nodes.contains(&child) {
+                taffy.children(child).unwrap_or_default()
+            } else {
+                vec![child]
+            }
+        })
+        .collect();
+    if effective != original_children {
+        // `set_children` below detaches the hoisted children from each
+        // contents child, so record those lists too for the restore pass.
+        for &child in &original_children {
+            if contents_nodes.contains(&child) {
+                rewritten.push((child, taffy.children(child).unwrap_or_default()));
+            }
+        }
+        rewritten.push((node, original_children));
+        let _ = taffy.set_children(node, &effective);
+    }
+}
+
+// Undo `apply_contents_flattening`, restoring every rewritten node's real
+// (logical) child list so subsequent `insertChild`/`getChild`/etc. calls see
+// the unflattened tree again. Entries are undone in reverse order of
+// recording, so each parent regains its wrapper before the wrapper regains
+// its own children.
+fn restore_contents_flattening(taffy: &mut TaffyTree, rewritten: Vec<(NodeId, Vec<NodeId>)>) {
+    for (node, original_children) in rewritten.into_iter().rev() {
+        let _ = taffy.set_children(node, &original_children);
+    }
+}